@@ -0,0 +1,85 @@
+//! State machine for a delimited ASCII protocol: messages are wrapped
+//! between a `start` and `end` marker byte, and each complete frame is
+//! acknowledged with a configurable `ack` byte. Bytes outside of a frame
+//! (before the first `start`, or stray bytes between frames) are dropped,
+//! and a partial frame is held until its `end` marker arrives.
+pub struct FrameDecoder {
+    pub start: u8,
+    pub end: u8,
+    pub ack: u8,
+    buffer: Vec<u8>,
+    in_frame: bool,
+}
+
+impl FrameDecoder {
+    pub fn new(start: u8, end: u8, ack: u8) -> Self {
+        FrameDecoder {
+            start,
+            end,
+            ack,
+            buffer: Vec::new(),
+            in_frame: false,
+        }
+    }
+
+    /// Feeds one byte in. Returns the frame contents (without the start/end
+    /// markers) once a complete frame has been seen.
+    pub fn feed(&mut self, byte: u8) -> Option<Vec<u8>> {
+        if !self.in_frame {
+            if byte == self.start {
+                self.in_frame = true;
+                self.buffer.clear();
+            }
+            None
+        } else if byte == self.end {
+            self.in_frame = false;
+            Some(std::mem::take(&mut self.buffer))
+        } else {
+            self.buffer.push(byte);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feed_passes_through_a_complete_frame() {
+        let mut decoder = FrameDecoder::new(b'<', b'>', 0x06);
+        assert_eq!(decoder.feed(b'<'), None);
+        assert_eq!(decoder.feed(b'h'), None);
+        assert_eq!(decoder.feed(b'i'), None);
+        assert_eq!(decoder.feed(b'>'), Some(b"hi".to_vec()));
+    }
+
+    #[test]
+    fn feed_drops_bytes_before_the_first_start_marker() {
+        let mut decoder = FrameDecoder::new(b'<', b'>', 0x06);
+        assert_eq!(decoder.feed(b'x'), None);
+        assert_eq!(decoder.feed(b'<'), None);
+        assert_eq!(decoder.feed(b'y'), None);
+        assert_eq!(decoder.feed(b'>'), Some(b"y".to_vec()));
+    }
+
+    #[test]
+    fn feed_treats_a_stray_start_byte_mid_frame_as_data() {
+        let mut decoder = FrameDecoder::new(b'<', b'>', 0x06);
+        decoder.feed(b'<');
+        decoder.feed(b'a');
+        decoder.feed(b'<');
+        assert_eq!(decoder.feed(b'>'), Some(b"a<".to_vec()));
+    }
+
+    #[test]
+    fn feed_starts_a_fresh_frame_after_one_completes() {
+        let mut decoder = FrameDecoder::new(b'<', b'>', 0x06);
+        decoder.feed(b'<');
+        decoder.feed(b'a');
+        decoder.feed(b'>');
+        assert_eq!(decoder.feed(b'<'), None);
+        assert_eq!(decoder.feed(b'b'), None);
+        assert_eq!(decoder.feed(b'>'), Some(b"b".to_vec()));
+    }
+}
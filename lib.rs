@@ -0,0 +1,338 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serialport::{DataBits, FlowControl, Parity, SerialPort, StopBits};
+
+pub mod frame;
+pub mod script;
+pub mod xmodem;
+
+/// Line configuration shared by every place the port gets (re)opened, so a
+/// baud change or a reader-thread reopen never silently resets the other
+/// fields back to 8N1-no-flow-control.
+#[derive(Clone, Copy, PartialEq)]
+pub struct LineSettings {
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+}
+
+impl Default for LineSettings {
+    fn default() -> Self {
+        LineSettings {
+            baud_rate: 115200,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+        }
+    }
+}
+
+impl LineSettings {
+    pub fn describe(&self) -> String {
+        let data_bits = match self.data_bits {
+            DataBits::Five => "5",
+            DataBits::Six => "6",
+            DataBits::Seven => "7",
+            DataBits::Eight => "8",
+        };
+        let parity = match self.parity {
+            Parity::None => "N",
+            Parity::Odd => "O",
+            Parity::Even => "E",
+        };
+        let stop_bits = match self.stop_bits {
+            StopBits::One => "1",
+            StopBits::Two => "2",
+        };
+        let flow = match self.flow_control {
+            FlowControl::None => "none",
+            FlowControl::Software => "software",
+            FlowControl::Hardware => "hardware",
+        };
+        format!(
+            "{} {}{}{}, flow control: {}",
+            self.baud_rate, data_bits, parity, stop_bits, flow
+        )
+    }
+}
+
+/// Opens `port_name` fresh with `settings`. Public so callers that hold
+/// their own handle outside of `Port` (e.g. the framed-mode ACK writer)
+/// can reopen it themselves after a `Port::reconfigure`.
+pub fn open_with_settings(port_name: &str, settings: &LineSettings) -> serialport::Result<Box<dyn SerialPort>> {
+    serialport::new(port_name, settings.baud_rate)
+        .data_bits(settings.data_bits)
+        .parity(settings.parity)
+        .stop_bits(settings.stop_bits)
+        .flow_control(settings.flow_control)
+        .timeout(Duration::from_millis(10))
+        .open()
+}
+
+/// Renders `data` the way `hexdump -C` does: an offset column, the bytes in
+/// hex grouped in two columns of eight, and an ASCII gutter on the right.
+pub fn hexdump(data: &[u8]) -> String {
+    let mut out = String::new();
+    for (row, chunk) in data.chunks(16).enumerate() {
+        let mut hex_cols = String::new();
+        for (i, byte) in chunk.iter().enumerate() {
+            if i == 8 {
+                hex_cols.push(' ');
+            }
+            hex_cols.push_str(&format!("{:02x} ", byte));
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        out.push_str(&format!("{:08x}  {:<49}|{}|\n", row * 16, hex_cols, ascii));
+    }
+    out
+}
+
+/// How `SessionLogger` renders each chunk it's given. `Raw` is a byte-for-byte
+/// passthrough; `Timestamped` prefixes each chunk with a millisecond marker
+/// but otherwise writes the bytes as-is; `Hex` adds the same timestamp but
+/// renders the chunk as a `hexdump`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LogMode {
+    Raw,
+    Timestamped,
+    Hex,
+}
+
+/// Captures bytes read from the serial port to a file, either as a raw
+/// passthrough or annotated with a per-chunk millisecond timestamp and
+/// (optionally) a hexdump rendering. Shared between the reader thread, which
+/// feeds it every byte it receives, and whatever toggles it on and off (the
+/// binary's command mode).
+pub struct SessionLogger {
+    sink: Option<File>,
+    mode: LogMode,
+    started: Instant,
+}
+
+impl SessionLogger {
+    pub fn new() -> Self {
+        SessionLogger {
+            sink: None,
+            mode: LogMode::Raw,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    pub fn start(&mut self, file: File, mode: LogMode) {
+        self.sink = Some(file);
+        self.mode = mode;
+        self.started = Instant::now();
+    }
+
+    pub fn stop(&mut self) {
+        self.sink = None;
+    }
+
+    pub fn log(&mut self, data: &[u8]) {
+        let Some(file) = self.sink.as_mut() else {
+            return;
+        };
+        match self.mode {
+            LogMode::Raw => {
+                let _ = file.write_all(data);
+            }
+            LogMode::Timestamped => {
+                let ts = self.started.elapsed().as_millis();
+                let _ = write!(file, "[{:>10}ms] ", ts);
+                let _ = file.write_all(data);
+                let _ = writeln!(file);
+            }
+            LogMode::Hex => {
+                let ts = self.started.elapsed().as_millis();
+                let _ = writeln!(file, "[{:>10}ms]\n{}", ts, hexdump(data));
+            }
+        }
+    }
+}
+
+impl Default for SessionLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The handful of operations a frontend needs from a serial link: (re)open
+/// it with a given configuration, write bytes out, and pull bytes in. `Port`
+/// is the one implementation, but code written against the trait (such as
+/// `script::run`) lets tests drive a mock instead of real hardware.
+pub trait SerialOperations {
+    fn open(&mut self) -> serialport::Result<()>;
+    fn write(&mut self, data: &[u8]) -> io::Result<()>;
+    fn receive(&mut self, buffer: &mut [u8]) -> io::Result<usize>;
+}
+
+/// Owns the boxed `dyn SerialPort`, the line settings it was opened with,
+/// and the background reader thread, so callers stop juggling three
+/// duplicated `serialport::new(...).open()` sites and a bare `JoinHandle`.
+///
+/// `settings` is shared (`Arc<Mutex<_>>`) rather than a plain field so that
+/// `reconfigure` can hand a baud/line-settings change to the reader thread
+/// without tearing it down: the thread notices the value changed and
+/// reopens its own handle to match, instead of quietly reading at the old
+/// configuration forever.
+pub struct Port {
+    port_name: String,
+    settings: Arc<Mutex<LineSettings>>,
+    handle: Option<Box<dyn SerialPort>>,
+    reader_handle: Option<JoinHandle<()>>,
+    reader_paused: Arc<AtomicBool>,
+}
+
+impl Port {
+    pub fn new(port_name: impl Into<String>, settings: LineSettings) -> Self {
+        Port {
+            port_name: port_name.into(),
+            settings: Arc::new(Mutex::new(settings)),
+            handle: None,
+            reader_handle: None,
+            reader_paused: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn port_name(&self) -> &str {
+        &self.port_name
+    }
+
+    pub fn settings(&self) -> LineSettings {
+        *self.settings.lock().unwrap()
+    }
+
+    /// Hands out the shared settings cell so a caller that keeps its own
+    /// handle alongside `Port` (e.g. the framed-mode ACK writer in the
+    /// binary) can notice a `reconfigure` and reopen to match it.
+    pub fn settings_handle(&self) -> Arc<Mutex<LineSettings>> {
+        Arc::clone(&self.settings)
+    }
+
+    /// Reopens the port with `settings` on success, leaving the previous
+    /// settings and handle untouched on failure. The background reader
+    /// thread (if any) picks up the change on its own and reopens its
+    /// handle to match.
+    pub fn reconfigure(&mut self, settings: LineSettings) -> serialport::Result<()> {
+        let handle = open_with_settings(&self.port_name, &settings)?;
+        self.handle = Some(handle);
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Gives XMODEM (or anything else that needs to bypass the reader
+    /// thread) direct, exclusive access to the open handle. Pair with
+    /// `pause_reader`/`resume_reader` so the two don't race for bytes.
+    pub fn inner_mut(&mut self) -> Option<&mut dyn SerialPort> {
+        match &mut self.handle {
+            Some(handle) => Some(&mut **handle),
+            None => None,
+        }
+    }
+
+    pub fn pause_reader(&self) {
+        self.reader_paused.store(true, Ordering::Release);
+    }
+
+    pub fn resume_reader(&self) {
+        self.reader_paused.store(false, Ordering::Release);
+    }
+
+    /// Opens an independent handle to the same port with the current
+    /// settings, e.g. so a background thread can write (such as a framed
+    /// protocol's ACK byte) without taking `&mut self`.
+    pub fn duplicate_handle(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        open_with_settings(&self.port_name, &self.settings())
+    }
+
+    /// Spawns the background thread that continuously reads from a second
+    /// handle to the same line, calling `on_data` with each non-empty chunk.
+    /// Reading backs off while `pause_reader` is in effect. Replaces any
+    /// previously spawned reader thread (it is simply dropped; it exits on
+    /// its own once its handle errors or the process exits).
+    ///
+    /// The thread watches the shared settings cell every pass and reopens
+    /// its handle whenever it no longer matches what it last read with, so
+    /// a `reconfigure` from the main thread (baud rate, line settings) is
+    /// reflected on the receive side too, not just on writes.
+    pub fn spawn_reader<F>(&mut self, mut on_data: F) -> serialport::Result<()>
+    where
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        let mut active_settings = self.settings();
+        let mut clone_handle = open_with_settings(&self.port_name, &active_settings)?;
+        let paused = Arc::clone(&self.reader_paused);
+        let port_name = self.port_name.clone();
+        let settings = Arc::clone(&self.settings);
+        let reader_handle = thread::spawn(move || {
+            let mut buffer = [0u8; 1024];
+            loop {
+                let current = *settings.lock().unwrap();
+                if current != active_settings {
+                    match open_with_settings(&port_name, &current) {
+                        Ok(handle) => {
+                            clone_handle = handle;
+                            active_settings = current;
+                        }
+                        Err(_) => {
+                            thread::sleep(Duration::from_millis(50));
+                            continue;
+                        }
+                    }
+                }
+                if paused.load(Ordering::Acquire) {
+                    thread::sleep(Duration::from_millis(20));
+                    continue;
+                }
+                match clone_handle.read(&mut buffer) {
+                    Ok(count) if count > 0 => on_data(&buffer[..count]),
+                    Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {
+                        // Do nothing on timeout
+                    }
+                    Err(_) => break,
+                    _ => {}
+                }
+            }
+        });
+        self.reader_handle = Some(reader_handle);
+        Ok(())
+    }
+}
+
+impl SerialOperations for Port {
+    fn open(&mut self) -> serialport::Result<()> {
+        let handle = open_with_settings(&self.port_name, &self.settings())?;
+        self.handle = Some(handle);
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.handle
+            .as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "port not open"))?
+            .write_all(data)
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.handle
+            .as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "port not open"))?
+            .read(buffer)
+    }
+}
@@ -1,14 +1,148 @@
-use std::io::{self, Read, Write};
-use std::time::Duration;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
-use serialport::{SerialPort, SerialPortType};
+use serialport::{DataBits, FlowControl, Parity, SerialPort, SerialPortType, StopBits};
 use termion::event::Key;
 use termion::input::TermRead;
 use termion::raw::IntoRawMode;
 
+use rustty::frame::FrameDecoder;
+use rustty::{open_with_settings, script, xmodem, LineSettings, LogMode, Port, SerialOperations, SessionLogger};
+
+/// What the main loop reads off the channel: either a raw keypress (from
+/// the keyboard thread, or an unframed byte from the serial reader thread)
+/// or a complete decoded frame (from the serial reader thread, when framed
+/// protocol mode is on).
+enum Event {
+    Key(Key),
+    Frame(Vec<u8>),
+}
+
+fn parse_byte(input: &str) -> Option<u8> {
+    let input = input.trim();
+    if let Some(hex) = input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+        u8::from_str_radix(hex, 16).ok()
+    } else {
+        input.parse::<u8>().ok()
+    }
+}
+
+fn parse_byte_sequence(input: &str) -> Option<Vec<u8>> {
+    input.split_whitespace().map(parse_byte).collect()
+}
+
+fn prompt_byte(label: &str, default: u8) -> u8 {
+    print!("{} [default 0x{:02X}]: ", label, default);
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    let _ = io::stdin().read_line(&mut input);
+    if input.trim().is_empty() {
+        default
+    } else {
+        parse_byte(&input).unwrap_or(default)
+    }
+}
+
+/// `--script <path>` switches the whole program from the interactive
+/// terminal to running a SEND/EXPECT/DELAY script against the port and
+/// exiting, so it can be used for non-interactive board provisioning.
+/// `--timeout <ms>` overrides the default per-`EXPECT` timeout (5000ms).
+/// `--port <name>` and `--baud <rate>` pick the port and line settings
+/// without prompting; `--port` defaults to the first port `available_ports`
+/// reports and `--baud` defaults to `LineSettings::default()`'s 115200, so a
+/// scripted run never waits on stdin the way the interactive prompts do.
+struct ScriptArgs {
+    path: String,
+    timeout: Duration,
+    port: Option<String>,
+    baud: Option<u32>,
+}
+
+fn parse_script_args() -> Option<ScriptArgs> {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args
+        .iter()
+        .position(|a| a == "--script")
+        .and_then(|i| args.get(i + 1))?
+        .clone();
+    let timeout = args
+        .iter()
+        .position(|a| a == "--timeout")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(Duration::from_secs(5));
+    let port = args
+        .iter()
+        .position(|a| a == "--port")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+    let baud = args
+        .iter()
+        .position(|a| a == "--baud")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse::<u32>().ok());
+    Some(ScriptArgs { path, timeout, port, baud })
+}
+
+fn prompt_data_bits() -> DataBits {
+    print!("Data bits [5-8, default 8]: ");
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    let _ = io::stdin().read_line(&mut input);
+    match input.trim() {
+        "5" => DataBits::Five,
+        "6" => DataBits::Six,
+        "7" => DataBits::Seven,
+        _ => DataBits::Eight,
+    }
+}
+
+fn prompt_parity() -> Parity {
+    print!("Parity [none/odd/even, default none]: ");
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    let _ = io::stdin().read_line(&mut input);
+    match input.trim().to_lowercase().as_str() {
+        "odd" => Parity::Odd,
+        "even" => Parity::Even,
+        _ => Parity::None,
+    }
+}
+
+fn prompt_stop_bits() -> StopBits {
+    print!("Stop bits [1/2, default 1]: ");
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    let _ = io::stdin().read_line(&mut input);
+    match input.trim() {
+        "2" => StopBits::Two,
+        _ => StopBits::One,
+    }
+}
+
+fn prompt_flow_control() -> FlowControl {
+    print!("Flow control [none/hardware/software, default none]: ");
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    let _ = io::stdin().read_line(&mut input);
+    match input.trim().to_lowercase().as_str() {
+        "hardware" | "hw" => FlowControl::Hardware,
+        "software" | "sw" => FlowControl::Software,
+        _ => FlowControl::None,
+    }
+}
+
 fn main() -> io::Result<()> {
+    // Checked up front: a scripted run must never block on the interactive
+    // prompts below (port index, baud, data/parity/stop/flow), since that
+    // would defeat the whole point of driving the program non-interactively.
+    let script_args = parse_script_args();
+
     // List available serial ports
     let available_ports = match serialport::available_ports() {
         Ok(ports) => ports,
@@ -23,87 +157,129 @@ fn main() -> io::Result<()> {
         return Ok(());
     }
 
-    // Format and display available serial ports in a table sized for 80x24 terminal
-    println!("\nAvailable serial ports:");
-    println!("┌─────┬──────────────────┬──────────┬──────────────────────────┐");
-    println!("│ Idx │ Port Name        │ Type     │ Details                  │");
-    println!("├─────┼──────────────────┼──────────┼──────────────────────────┤");
-    
-    for (i, port) in available_ports.iter().enumerate() {
-        let port_name = format!("{}", port.port_name);
-        let port_name = if port_name.len() > 16 { 
-            format!("{}...", &port_name[0..13]) 
-        } else { 
-            format!("{:<16}", port_name) 
+    let (port_name, settings) = if let Some(script_args) = &script_args {
+        let port_name = script_args
+            .port
+            .clone()
+            .unwrap_or_else(|| available_ports[0].port_name.clone());
+        let mut settings = LineSettings::default();
+        if let Some(baud) = script_args.baud {
+            settings.baud_rate = baud;
+        }
+        (port_name, settings)
+    } else {
+        // Format and display available serial ports in a table sized for 80x24 terminal
+        println!("\nAvailable serial ports:");
+        println!("┌─────┬──────────────────┬──────────┬──────────────────────────┐");
+        println!("│ Idx │ Port Name        │ Type     │ Details                  │");
+        println!("├─────┼──────────────────┼──────────┼──────────────────────────┤");
+
+        for (i, port) in available_ports.iter().enumerate() {
+            let port_name = port.port_name.clone();
+            let port_name = if port_name.len() > 16 {
+                format!("{}...", &port_name[0..13])
+            } else {
+                format!("{:<16}", port_name)
+            };
+
+            let (port_type, details) = match &port.port_type {
+                SerialPortType::UsbPort(info) => {
+                    ("USB", format!("VID:{:04x} PID:{:04x}",
+                        info.vid, info.pid))
+                }
+                SerialPortType::BluetoothPort => {
+                    ("Bluetooth", String::from("N/A"))
+                }
+                SerialPortType::PciPort => {
+                    ("PCI", String::from("N/A"))
+                }
+                _ => {
+                    ("Unknown", String::from("N/A"))
+                }
+            };
+
+            let details = if details.len() > 24 {
+                format!("{}...", &details[0..21])
+            } else {
+                format!("{:<24}", details)
+            };
+
+            println!("│ {:3} │ {} │ {:<8} │ {} │",
+                     i, port_name, port_type, details);
+        }
+
+        println!("└─────┴──────────────────┴──────────┴──────────────────────────┘");
+
+        print!("Select port [0-{}]: ", available_ports.len() - 1);
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let port_index = input.trim().parse::<usize>().unwrap_or(0);
+
+        if port_index >= available_ports.len() {
+            println!("Invalid selection, using port 0.");
+        }
+
+        let port_name = available_ports[port_index.min(available_ports.len() - 1)].port_name.clone();
+
+        println!("\nAvailable baud rates: 9600, 19200, 38400, 57600, 115200");
+        print!("Select baud rate [115200]: ");
+        io::stdout().flush()?;
+
+        input.clear();
+        io::stdin().read_line(&mut input)?;
+        let baud_rate = input.trim().parse::<u32>().unwrap_or(115200);
+
+        let settings = LineSettings {
+            baud_rate,
+            data_bits: prompt_data_bits(),
+            parity: prompt_parity(),
+            stop_bits: prompt_stop_bits(),
+            flow_control: prompt_flow_control(),
         };
-        
-        let (port_type, details) = match &port.port_type {
-            SerialPortType::UsbPort(info) => {
-                ("USB", format!("VID:{:04x} PID:{:04x}", 
-                    info.vid, info.pid))
+
+        (port_name, settings)
+    };
+
+    println!("Opening {} at {}", port_name, settings.describe());
+
+    let mut port = Port::new(port_name, settings);
+    if let Err(e) = port.open() {
+        eprintln!("Failed to open port: {}", e);
+        return Ok(());
+    }
+
+    if let Some(script_args) = script_args {
+        let source = match std::fs::read_to_string(&script_args.path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("Failed to read script {}: {}", script_args.path, e);
+                std::process::exit(1);
             }
-            SerialPortType::BluetoothPort => {
-                ("Bluetooth", String::from("N/A"))
+        };
+        let steps = match script::parse(&source) {
+            Ok(steps) => steps,
+            Err(e) => {
+                eprintln!("Failed to parse script: {}", e);
+                std::process::exit(1);
             }
-            SerialPortType::PciPort => {
-                ("PCI", String::from("N/A"))
+        };
+        match script::run(&mut port, &steps, script_args.timeout) {
+            Ok(()) => {
+                println!("Script completed successfully.");
+                return Ok(());
             }
-            _ => {
-                ("Unknown", String::from("N/A"))
+            Err(e) => {
+                eprintln!("Script failed: {}", e);
+                std::process::exit(1);
             }
-        };
-        
-        let details = if details.len() > 24 { 
-            format!("{}...", &details[0..21]) 
-        } else { 
-            format!("{:<24}", details) 
-        };
-        
-        println!("│ {:3} │ {} │ {:<8} │ {} │", 
-                 i, port_name, port_type, details);
-    }
-    
-    println!("└─────┴──────────────────┴──────────┴──────────────────────────┘");
-
-    print!("Select port [0-{}]: ", available_ports.len() - 1);
-    io::stdout().flush()?;
-    
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    let port_index = input.trim().parse::<usize>().unwrap_or(0);
-    
-    if port_index >= available_ports.len() {
-        println!("Invalid selection, using port 0.");
-    }
-    
-    let port_name = &available_ports[port_index.min(available_ports.len() - 1)].port_name;
-    
-    println!("\nAvailable baud rates: 9600, 19200, 38400, 57600, 115200");
-    print!("Select baud rate [115200]: ");
-    io::stdout().flush()?;
-    
-    input.clear();
-    io::stdin().read_line(&mut input)?;
-    let baud_rate = input.trim().parse::<u32>().unwrap_or(115200);
-    
-    println!("Opening {} at {} baud", port_name, baud_rate);
-    
-    // Open the serial port
-    let port = serialport::new(port_name, baud_rate)
-        .timeout(Duration::from_millis(10))
-        .open();
-
-    let mut port = match port {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("Failed to open port: {}", e);
-            return Ok(());
         }
-    };
+    }
 
     println!("Serial port opened successfully.");
     println!("Press Ctrl+X to exit, Ctrl+T for command mode.");
-    println!("Command mode: 'b' to change baud rate, 'c' to clear screen");
+    println!("Command mode: 'b' baud, 's' line settings, 'l' toggle logging, 'u' XMODEM upload, 'd' XMODEM download, 'f' toggle framed mode, 't' toggle DTR, 'g' toggle RTS, 'k' send BREAK, 'r' reset sequence, 'c' clear screen");
     thread::sleep(Duration::from_millis(1000));
 
     // Set up terminal
@@ -113,74 +289,109 @@ fn main() -> io::Result<()> {
     // Set up channels for communication between threads
     let (tx, rx) = mpsc::channel();
 
+    let logger = Arc::new(Mutex::new(SessionLogger::new()));
+    let frame_decoder: Arc<Mutex<Option<FrameDecoder>>> = Arc::new(Mutex::new(None));
+
     // Thread for reading keyboard input
     let tx_clone = tx.clone();
     thread::spawn(move || {
         let stdin = io::stdin();
-        let mut keys = stdin.keys();
-        while let Some(result) = keys.next() {
-            if let Ok(key) = result {
-                if tx_clone.send(key).is_err() {
-                    break;
-                }
-                
-                // Exit if Ctrl+X is pressed
-                if key == Key::Ctrl('x') {
-                    break;
-                }
+        let keys = stdin.keys();
+        for key in keys.flatten() {
+            let exit = key == Key::Ctrl('x');
+            if tx_clone.send(Event::Key(key)).is_err() {
+                break;
+            }
+            if exit {
+                break;
             }
         }
     });
 
-    // Thread for reading from serial port
+    // Thread for reading from the serial port, owned and paused/resumed by `port`.
+    // Holds its own write handle so it can send a framed-protocol ACK without
+    // needing `&mut` access to `port` from the main thread. The handle is
+    // opened lazily and reopened whenever the shared settings cell no longer
+    // matches what it was last opened with, so a `reconfigure` (baud or line
+    // settings change) from the main thread doesn't leave it ACKing stale.
+    let ack_port_name = port.port_name().to_string();
+    let ack_settings_cell = port.settings_handle();
+    let mut ack_handle: Option<Box<dyn SerialPort>> = None;
+    let mut ack_handle_settings: Option<LineSettings> = None;
     let tx_clone = tx.clone();
-    let mut port_clone = match serialport::new(port_name, baud_rate)
-        .timeout(Duration::from_millis(10))
-        .open() {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("Failed to open port clone: {}", e);
-            return Ok(());
-        }
-    };
+    let logger_clone = Arc::clone(&logger);
+    let frame_decoder_clone = Arc::clone(&frame_decoder);
+    if let Err(e) = port.spawn_reader(move |data| {
+        // Logged unconditionally: framed mode still decodes these bytes
+        // below, but the capture promised by "log every received byte"
+        // shouldn't go dark just because a decoder is also consuming them.
+        logger_clone.lock().unwrap().log(data);
 
-    thread::spawn(move || {
-        let mut buffer = [0u8; 1024];
-        loop {
-            match port_clone.read(&mut buffer) {
-                Ok(count) if count > 0 => {
-                    for i in 0..count {
-                        let _ = tx_clone.send(Key::Char(buffer[i] as char));
+        let mut frames: Vec<(Vec<u8>, u8)> = Vec::new();
+        {
+            let mut decoder = frame_decoder_clone.lock().unwrap();
+            match decoder.as_mut() {
+                Some(decoder) => {
+                    for &byte in data {
+                        if let Some(frame) = decoder.feed(byte) {
+                            frames.push((frame, decoder.ack));
+                        }
                     }
                 }
-                Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {
-                    // Do nothing on timeout
+                None => {
+                    drop(decoder);
+                    for &byte in data {
+                        let _ = tx_clone.send(Event::Key(Key::Char(byte as char)));
+                    }
                 }
-                Err(e) => {
-                    eprintln!("Error reading from serial port: {}", e);
-                    break;
+            }
+        }
+        for (frame, ack) in frames {
+            let current_settings = *ack_settings_cell.lock().unwrap();
+            if ack_handle_settings != Some(current_settings) {
+                match open_with_settings(&ack_port_name, &current_settings) {
+                    Ok(handle) => {
+                        ack_handle = Some(handle);
+                        ack_handle_settings = Some(current_settings);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to reopen ACK handle for framed mode: {}", e);
+                    }
                 }
-                _ => {}
             }
+            if let Some(handle) = ack_handle.as_deref_mut() {
+                let _ = handle.write_all(&[ack]);
+            }
+            let _ = tx_clone.send(Event::Frame(frame));
         }
-    });
+    }) {
+        eprintln!("Failed to open port clone: {}", e);
+        return Ok(());
+    }
 
     let mut command_mode = false;
+    let mut dtr_asserted = false;
+    let mut rts_asserted = false;
+    let mut reset_sequence: Option<Vec<u8>> = None;
 
     // Main loop
     loop {
         match rx.recv() {
-            Ok(Key::Ctrl('x')) => {
+            Ok(Event::Frame(bytes)) => {
+                write!(stdout, "\r\n[frame] {}\r\n", String::from_utf8_lossy(&bytes))?;
+                stdout.flush()?;
+            }
+            Ok(Event::Key(Key::Ctrl('x'))) => {
                 write!(stdout, "\r\nExiting...\r\n")?;
                 stdout.flush()?;
                 break;
             }
-            Ok(Key::Ctrl('t')) => {
+            Ok(Event::Key(Key::Ctrl('t'))) => {
                 command_mode = true;
                 write!(stdout, "\r\n[Command Mode] ")?;
                 stdout.flush()?;
             }
-            Ok(key) => {
+            Ok(Event::Key(key)) => {
                 if command_mode {
                     match key {
                         Key::Char('b') => {
@@ -188,22 +399,192 @@ fn main() -> io::Result<()> {
                             stdout.flush()?;
                             let mut baud_input = String::new();
                             io::stdin().read_line(&mut baud_input)?;
-                            
+
                             if let Ok(new_baud) = baud_input.trim().parse::<u32>() {
                                 write!(stdout, "\r\nChanging baud rate to {}\r\n", new_baud)?;
-                                port = match serialport::new(port_name, new_baud)
-                                    .timeout(Duration::from_millis(10))
-                                    .open() {
-                                    Ok(p) => p,
-                                    Err(e) => {
-                                        write!(stdout, "\r\nFailed to change baud rate: {}\r\n", e)?;
-                                        port
-                                    }
-                                };
+                                let mut candidate = port.settings();
+                                candidate.baud_rate = new_baud;
+                                if let Err(e) = port.reconfigure(candidate) {
+                                    write!(stdout, "\r\nFailed to change baud rate: {}\r\n", e)?;
+                                }
                             } else {
                                 write!(stdout, "\r\nInvalid baud rate\r\n")?;
                             }
                         }
+                        Key::Char('s') => {
+                            write!(stdout, "\r\n")?;
+                            stdout.flush()?;
+                            let mut candidate = port.settings();
+                            candidate.data_bits = prompt_data_bits();
+                            candidate.parity = prompt_parity();
+                            candidate.stop_bits = prompt_stop_bits();
+                            candidate.flow_control = prompt_flow_control();
+                            write!(stdout, "\r\nChanging line settings to {}\r\n", candidate.describe())?;
+                            if let Err(e) = port.reconfigure(candidate) {
+                                write!(stdout, "\r\nFailed to change line settings: {}\r\n", e)?;
+                            }
+                        }
+                        Key::Char('l') => {
+                            let mut log = logger.lock().unwrap();
+                            if log.is_active() {
+                                log.stop();
+                                write!(stdout, "\r\nLogging stopped\r\n")?;
+                            } else {
+                                drop(log);
+                                write!(stdout, "\r\nLog file path: ")?;
+                                stdout.flush()?;
+                                let mut path_input = String::new();
+                                io::stdin().read_line(&mut path_input)?;
+                                let path = path_input.trim();
+
+                                write!(stdout, "\r\nMode [raw/timestamped/hex, default raw]: ")?;
+                                stdout.flush()?;
+                                let mut mode_input = String::new();
+                                io::stdin().read_line(&mut mode_input)?;
+                                let (mode, mode_label) = match mode_input.trim().to_lowercase().as_str() {
+                                    "hex" | "hexdump" => (LogMode::Hex, "hex"),
+                                    "timestamped" | "ts" => (LogMode::Timestamped, "timestamped"),
+                                    _ => (LogMode::Raw, "raw"),
+                                };
+
+                                if path.is_empty() {
+                                    write!(stdout, "\r\nNo path given, logging not started\r\n")?;
+                                } else {
+                                    match OpenOptions::new().create(true).append(true).open(path) {
+                                        Ok(file) => {
+                                            logger.lock().unwrap().start(file, mode);
+                                            write!(stdout, "\r\nLogging to {} ({})\r\n", path, mode_label)?;
+                                        }
+                                        Err(e) => {
+                                            write!(stdout, "\r\nFailed to open log file: {}\r\n", e)?;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        Key::Char('u') => {
+                            write!(stdout, "\r\nFile to send: ")?;
+                            stdout.flush()?;
+                            let mut path_input = String::new();
+                            io::stdin().read_line(&mut path_input)?;
+                            match std::fs::read(path_input.trim()) {
+                                Ok(data) => {
+                                    write!(stdout, "\r\nSending {} bytes via XMODEM...\r\n", data.len())?;
+                                    stdout.flush()?;
+                                    port.pause_reader();
+                                    let result = match port.inner_mut() {
+                                        Some(handle) => xmodem::send(handle, &data),
+                                        None => Err(io::Error::new(io::ErrorKind::NotConnected, "port not open")),
+                                    };
+                                    port.resume_reader();
+                                    match result {
+                                        Ok(()) => write!(stdout, "\r\nXMODEM send complete\r\n")?,
+                                        Err(e) => write!(stdout, "\r\nXMODEM send failed: {}\r\n", e)?,
+                                    }
+                                }
+                                Err(e) => {
+                                    write!(stdout, "\r\nFailed to read file: {}\r\n", e)?;
+                                }
+                            }
+                        }
+                        Key::Char('d') => {
+                            write!(stdout, "\r\nSave received file as: ")?;
+                            stdout.flush()?;
+                            let mut path_input = String::new();
+                            io::stdin().read_line(&mut path_input)?;
+                            let path = path_input.trim().to_string();
+
+                            write!(stdout, "\r\nWaiting for XMODEM sender...\r\n")?;
+                            stdout.flush()?;
+                            port.pause_reader();
+                            let result = match port.inner_mut() {
+                                Some(handle) => xmodem::receive(handle),
+                                None => Err(io::Error::new(io::ErrorKind::NotConnected, "port not open")),
+                            };
+                            port.resume_reader();
+                            match result {
+                                Ok(data) => match std::fs::write(&path, &data) {
+                                    Ok(()) => write!(stdout, "\r\nReceived {} bytes, saved to {}\r\n", data.len(), path)?,
+                                    Err(e) => write!(stdout, "\r\nReceived {} bytes but failed to save: {}\r\n", data.len(), e)?,
+                                },
+                                Err(e) => write!(stdout, "\r\nXMODEM receive failed: {}\r\n", e)?,
+                            }
+                        }
+                        Key::Char('f') => {
+                            let mut decoder = frame_decoder.lock().unwrap();
+                            if decoder.is_some() {
+                                *decoder = None;
+                                write!(stdout, "\r\nFramed protocol mode disabled\r\n")?;
+                            } else {
+                                drop(decoder);
+                                let start = prompt_byte("Start marker byte", b'<');
+                                let end = prompt_byte("End marker byte", b'>');
+                                let ack = prompt_byte("ACK byte", 0x06);
+                                *frame_decoder.lock().unwrap() = Some(FrameDecoder::new(start, end, ack));
+                                write!(stdout, "\r\nFramed protocol mode enabled (start=0x{:02X}, end=0x{:02X}, ack=0x{:02X})\r\n", start, end, ack)?;
+                            }
+                        }
+                        Key::Char('t') => {
+                            let desired = !dtr_asserted;
+                            match port.inner_mut() {
+                                Some(handle) => match handle.write_data_terminal_ready(desired) {
+                                    Ok(()) => {
+                                        dtr_asserted = desired;
+                                        write!(stdout, "\r\nDTR {}\r\n", if dtr_asserted { "asserted" } else { "cleared" })?;
+                                    }
+                                    Err(e) => write!(stdout, "\r\nFailed to set DTR: {}\r\n", e)?,
+                                },
+                                None => write!(stdout, "\r\nPort not open\r\n")?,
+                            }
+                        }
+                        Key::Char('g') => {
+                            let desired = !rts_asserted;
+                            match port.inner_mut() {
+                                Some(handle) => match handle.write_request_to_send(desired) {
+                                    Ok(()) => {
+                                        rts_asserted = desired;
+                                        write!(stdout, "\r\nRTS {}\r\n", if rts_asserted { "asserted" } else { "cleared" })?;
+                                    }
+                                    Err(e) => write!(stdout, "\r\nFailed to set RTS: {}\r\n", e)?,
+                                },
+                                None => write!(stdout, "\r\nPort not open\r\n")?,
+                            }
+                        }
+                        Key::Char('k') => match port.inner_mut() {
+                            Some(handle) => {
+                                let result = handle.set_break().and_then(|()| {
+                                    thread::sleep(Duration::from_millis(250));
+                                    handle.clear_break()
+                                });
+                                match result {
+                                    Ok(()) => write!(stdout, "\r\nBREAK sent\r\n")?,
+                                    Err(e) => write!(stdout, "\r\nFailed to send BREAK: {}\r\n", e)?,
+                                }
+                            }
+                            None => write!(stdout, "\r\nPort not open\r\n")?,
+                        },
+                        Key::Char('r') => {
+                            write!(stdout, "\r\nReset byte sequence (space-separated hex/dec, blank to reuse previous): ")?;
+                            stdout.flush()?;
+                            let mut seq_input = String::new();
+                            io::stdin().read_line(&mut seq_input)?;
+                            let trimmed = seq_input.trim();
+                            let sequence = if trimmed.is_empty() {
+                                reset_sequence.clone()
+                            } else {
+                                parse_byte_sequence(trimmed)
+                            };
+                            match sequence {
+                                Some(bytes) if !bytes.is_empty() => match port.write(&bytes) {
+                                    Ok(()) => {
+                                        reset_sequence = Some(bytes);
+                                        write!(stdout, "\r\nReset sequence sent\r\n")?;
+                                    }
+                                    Err(e) => write!(stdout, "\r\nFailed to send reset sequence: {}\r\n", e)?,
+                                },
+                                _ => write!(stdout, "\r\nNo reset sequence configured\r\n")?,
+                            }
+                        }
                         Key::Char('c') => {
                             write!(stdout, "\x1B[2J\x1B[1;1H")?; // Clear screen and move cursor to top
                         }
@@ -214,19 +595,14 @@ fn main() -> io::Result<()> {
                     command_mode = false;
                     write!(stdout, "[Terminal Mode]\r\n")?;
                     stdout.flush()?;
-                } else {
-                    match key {
-                        Key::Char(c) => {
-                            // Send character to serial port
-                            if let Err(e) = port.write_all(&[c as u8]) {
-                                write!(stdout, "\r\nError writing to port: {}\r\n", e)?;
-                            } else {
-                                // Echo character to terminal
-                                write!(stdout, "{}", c)?;
-                                stdout.flush()?;
-                            }
-                        }
-                        _ => {}
+                } else if let Key::Char(c) = key {
+                    // Send character to serial port
+                    if let Err(e) = port.write(&[c as u8]) {
+                        write!(stdout, "\r\nError writing to port: {}\r\n", e)?;
+                    } else {
+                        // Echo character to terminal
+                        write!(stdout, "{}", c)?;
+                        stdout.flush()?;
                     }
                 }
             }
@@ -0,0 +1,193 @@
+//! A tiny SEND/EXPECT/DELAY scripting language for driving a port
+//! non-interactively, e.g. for board provisioning. Borrows the
+//! request/reply discipline of a scripted command-and-response session:
+//! each `SEND` pushes bytes out, each `EXPECT` blocks until a matching byte
+//! sequence shows up in what comes back (or the step's timeout elapses),
+//! and `DELAY` just sleeps.
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::SerialOperations;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Step {
+    Send(Vec<u8>),
+    Expect(Vec<u8>),
+    Delay(Duration),
+}
+
+/// Parses a script, one directive per non-empty, non-comment line:
+/// `SEND <bytes>`, `EXPECT <pattern>`, or `DELAY <ms>`. `SEND`/`EXPECT`
+/// bodies support `\n`, `\r`, `\t`, `\\` and `\xNN` hex-byte escapes.
+pub fn parse(source: &str) -> Result<Vec<Step>, String> {
+    source
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_no, line)| parse_line(line).map_err(|e| format!("line {}: {}", line_no, e)))
+        .collect()
+}
+
+fn parse_line(line: &str) -> Result<Step, String> {
+    if let Some(rest) = line.strip_prefix("SEND ") {
+        Ok(Step::Send(decode_escapes(rest)?))
+    } else if let Some(rest) = line.strip_prefix("EXPECT ") {
+        Ok(Step::Expect(decode_escapes(rest)?))
+    } else if let Some(rest) = line.strip_prefix("DELAY ") {
+        let ms: u64 = rest.trim().parse().map_err(|_| format!("invalid DELAY value: {}", rest))?;
+        Ok(Step::Delay(Duration::from_millis(ms)))
+    } else {
+        Err(format!("unrecognized directive: {}", line))
+    }
+}
+
+fn decode_escapes(input: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0u8; 4];
+            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push(b'\n'),
+            Some('r') => out.push(b'\r'),
+            Some('t') => out.push(b'\t'),
+            Some('\\') => out.push(b'\\'),
+            Some('x') => {
+                let hi = chars.next().ok_or("truncated \\x escape")?;
+                let lo = chars.next().ok_or("truncated \\x escape")?;
+                let byte = u8::from_str_radix(&format!("{}{}", hi, lo), 16)
+                    .map_err(|_| format!("invalid hex escape \\x{}{}", hi, lo))?;
+                out.push(byte);
+            }
+            Some(other) => return Err(format!("unknown escape \\{}", other)),
+            None => return Err("trailing backslash".to_string()),
+        }
+    }
+    Ok(out)
+}
+
+/// Runs `steps` against `port`, using `default_timeout` for every `EXPECT`.
+/// Returns an error naming the first step that failed to match in time.
+pub fn run<P: SerialOperations>(port: &mut P, steps: &[Step], default_timeout: Duration) -> Result<(), String> {
+    for (index, step) in steps.iter().enumerate() {
+        match step {
+            Step::Send(bytes) => port.write(bytes).map_err(|e| format!("step {}: send failed: {}", index + 1, e))?,
+            Step::Expect(pattern) => {
+                wait_for(port, pattern, default_timeout)
+                    .map_err(|e| format!("step {}: {}", index + 1, e))?;
+            }
+            Step::Delay(duration) => thread::sleep(*duration),
+        }
+    }
+    Ok(())
+}
+
+fn wait_for<P: SerialOperations>(port: &mut P, pattern: &[u8], timeout: Duration) -> Result<(), String> {
+    let deadline = Instant::now() + timeout;
+    let mut accumulated = Vec::new();
+    let mut buffer = [0u8; 256];
+    while Instant::now() < deadline {
+        match port.receive(&mut buffer) {
+            Ok(0) => {}
+            Ok(count) => {
+                accumulated.extend_from_slice(&buffer[..count]);
+                if contains(&accumulated, pattern) {
+                    return Ok(());
+                }
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(format!("read error while waiting for {:?}: {}", String::from_utf8_lossy(pattern), e)),
+        }
+    }
+    Err(format!("timed out waiting for {:?}", String::from_utf8_lossy(pattern)))
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    if needle.is_empty() {
+        return true;
+    }
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_escapes_handles_all_escapes() {
+        assert_eq!(decode_escapes("a\\nb\\r\\t\\\\\\x41").unwrap(), b"a\nb\r\t\\A");
+    }
+
+    #[test]
+    fn decode_escapes_rejects_unknown_escape() {
+        assert!(decode_escapes("\\q").is_err());
+    }
+
+    #[test]
+    fn decode_escapes_rejects_truncated_hex_escape() {
+        assert!(decode_escapes("\\x4").is_err());
+    }
+
+    #[test]
+    fn parse_skips_blank_lines_and_comments() {
+        let steps = parse("# comment\n\nSEND ab\nDELAY 10\nEXPECT cd\n").unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                Step::Send(vec![b'a', b'b']),
+                Step::Delay(Duration::from_millis(10)),
+                Step::Expect(vec![b'c', b'd']),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_reports_line_number_on_error() {
+        let err = parse("SEND ok\nBOGUS\n").unwrap_err();
+        assert!(err.starts_with("line 2:"), "unexpected error: {}", err);
+    }
+
+    struct MockPort {
+        outgoing: Vec<u8>,
+        incoming: Vec<u8>,
+    }
+
+    impl SerialOperations for MockPort {
+        fn open(&mut self) -> serialport::Result<()> {
+            Ok(())
+        }
+
+        fn write(&mut self, data: &[u8]) -> io::Result<()> {
+            self.outgoing.extend_from_slice(data);
+            Ok(())
+        }
+
+        fn receive(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let count = self.incoming.len().min(buffer.len());
+            buffer[..count].copy_from_slice(&self.incoming[..count]);
+            self.incoming.drain(..count);
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn run_succeeds_when_expected_bytes_arrive() {
+        let steps = vec![Step::Send(vec![b'?']), Step::Expect(vec![b'O', b'K'])];
+        let mut port = MockPort { outgoing: Vec::new(), incoming: b"OK".to_vec() };
+        assert!(run(&mut port, &steps, Duration::from_millis(100)).is_ok());
+        assert_eq!(port.outgoing, vec![b'?']);
+    }
+
+    #[test]
+    fn run_fails_when_expected_bytes_never_arrive() {
+        let steps = vec![Step::Expect(vec![b'O', b'K'])];
+        let mut port = MockPort { outgoing: Vec::new(), incoming: Vec::new() };
+        let err = run(&mut port, &steps, Duration::from_millis(20)).unwrap_err();
+        assert!(err.contains("timed out"), "unexpected error: {}", err);
+    }
+}
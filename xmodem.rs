@@ -0,0 +1,194 @@
+//! Minimal XMODEM/XMODEM-CRC implementation. Callers are expected to hand
+//! over an exclusively-owned port (e.g. via `Port::pause_reader` plus
+//! `Port::inner_mut`) for the duration of the transfer.
+use serialport::SerialPort;
+use std::io;
+use std::time::{Duration, Instant};
+
+const SOH: u8 = 0x01;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+const CRC_MODE: u8 = b'C';
+const PAD: u8 = 0x1A;
+const BLOCK_SIZE: usize = 128;
+const MAX_RETRIES: u32 = 10;
+
+fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            if crc & 0x8000 != 0 {
+                crc = (crc << 1) ^ 0x1021;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |acc, &b| acc.wrapping_add(b))
+}
+
+fn read_byte(port: &mut dyn SerialPort, timeout: Duration) -> Option<u8> {
+    let deadline = Instant::now() + timeout;
+    let mut byte = [0u8; 1];
+    while Instant::now() < deadline {
+        match port.read(&mut byte) {
+            Ok(1) => return Some(byte[0]),
+            Ok(_) => {}
+            Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {}
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+/// Sends `data` to the peer over an already-open, exclusively-owned port.
+pub fn send(port: &mut dyn SerialPort, data: &[u8]) -> io::Result<()> {
+    let use_crc = match read_byte(port, Duration::from_secs(60)) {
+        Some(CRC_MODE) => true,
+        Some(NAK) => false,
+        _ => return Err(io::Error::new(io::ErrorKind::TimedOut, "no response from receiver")),
+    };
+
+    for (index, chunk) in data.chunks(BLOCK_SIZE).enumerate() {
+        let block_num = ((index + 1) % 256) as u8;
+        let mut payload = [PAD; BLOCK_SIZE];
+        payload[..chunk.len()].copy_from_slice(chunk);
+
+        let mut frame = Vec::with_capacity(BLOCK_SIZE + 5);
+        frame.push(SOH);
+        frame.push(block_num);
+        frame.push(255u8.wrapping_sub(block_num));
+        frame.extend_from_slice(&payload);
+        if use_crc {
+            let crc = crc16_ccitt(&payload);
+            frame.push((crc >> 8) as u8);
+            frame.push(crc as u8);
+        } else {
+            frame.push(checksum(&payload));
+        }
+
+        let mut acked = false;
+        for _ in 0..MAX_RETRIES {
+            port.write_all(&frame)?;
+            match read_byte(port, Duration::from_secs(10)) {
+                Some(ACK) => {
+                    acked = true;
+                    break;
+                }
+                Some(CAN) => return Err(io::Error::other("transfer cancelled by receiver")),
+                _ => continue, // NAK or timeout: retry the block
+            }
+        }
+        if !acked {
+            return Err(io::Error::other("receiver NAKed block too many times"));
+        }
+    }
+
+    for _ in 0..MAX_RETRIES {
+        port.write_all(&[EOT])?;
+        if read_byte(port, Duration::from_secs(10)) == Some(ACK) {
+            return Ok(());
+        }
+    }
+    Err(io::Error::other("EOT was never acknowledged"))
+}
+
+/// Receives a file from the peer, returning the payload with trailing
+/// `0x1A` pad bytes from the final block stripped.
+pub fn receive(port: &mut dyn SerialPort) -> io::Result<Vec<u8>> {
+    let mut use_crc = true;
+    let mut first_header = None;
+    for attempt in 0..MAX_RETRIES {
+        port.write_all(&[if attempt < MAX_RETRIES / 2 { CRC_MODE } else { use_crc = false; NAK }])?;
+        if let Some(byte) = read_byte(port, Duration::from_secs(3)) {
+            first_header = Some(byte);
+            break;
+        }
+    }
+    if first_header.is_none() {
+        return Err(io::Error::new(io::ErrorKind::TimedOut, "no response from sender"));
+    }
+
+    let mut data = Vec::new();
+    let mut expected_block: u8 = 1;
+    loop {
+        match first_header.take().or_else(|| read_byte(port, Duration::from_secs(10))) {
+            Some(SOH) => {}
+            Some(EOT) => {
+                port.write_all(&[ACK])?;
+                break;
+            }
+            Some(CAN) => return Err(io::Error::other("transfer cancelled by sender")),
+            _ => return Err(io::Error::new(io::ErrorKind::TimedOut, "timed out waiting for block")),
+        }
+
+        let block_num = read_byte(port, Duration::from_secs(2))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "timed out reading block number"))?;
+        let block_num_inv = read_byte(port, Duration::from_secs(2))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "timed out reading block number complement"))?;
+
+        let mut payload = [0u8; BLOCK_SIZE];
+        for byte in payload.iter_mut() {
+            *byte = read_byte(port, Duration::from_secs(2))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "timed out reading block data"))?;
+        }
+
+        let valid_header = block_num_inv == 255u8.wrapping_sub(block_num);
+        let valid_payload = if use_crc {
+            let expected_crc = crc16_ccitt(&payload);
+            let hi = read_byte(port, Duration::from_secs(2))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "timed out reading CRC"))?;
+            let lo = read_byte(port, Duration::from_secs(2))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "timed out reading CRC"))?;
+            ((hi as u16) << 8 | lo as u16) == expected_crc
+        } else {
+            let expected_checksum = checksum(&payload);
+            let received = read_byte(port, Duration::from_secs(2))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "timed out reading checksum"))?;
+            received == expected_checksum
+        };
+
+        if valid_header && valid_payload && block_num == expected_block {
+            data.extend_from_slice(&payload);
+            expected_block = expected_block.wrapping_add(1);
+            port.write_all(&[ACK])?;
+        } else if valid_header && valid_payload && block_num == expected_block.wrapping_sub(1) {
+            // Sender retransmitted a block we already ACKed; ACK again without
+            // re-appending it.
+            port.write_all(&[ACK])?;
+        } else {
+            port.write_all(&[NAK])?;
+        }
+    }
+
+    while data.last() == Some(&PAD) {
+        data.pop();
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_wraps_on_overflow() {
+        assert_eq!(checksum(&[0xFF, 0x01]), 0x00);
+        assert_eq!(checksum(&[1, 2, 3]), 6);
+    }
+
+    #[test]
+    fn crc16_ccitt_matches_the_standard_check_value() {
+        // "123456789" is the standard CRC check string; the CRC-16/XMODEM
+        // reference implementation (poly 0x1021, init 0x0000, no reflect,
+        // no xorout -- exactly what this function computes) gives 0x31C3.
+        assert_eq!(crc16_ccitt(b"123456789"), 0x31C3);
+    }
+}